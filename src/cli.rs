@@ -0,0 +1,38 @@
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+#[command(name = "factorio-mod-manager", about = "Manage mods for a Factorio headless server")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Check every installed mod for updates and install anything the manifest/dependencies require (default)
+    Update,
+    /// Install (or reinstall) a single mod by name
+    Install {
+        /// Mod name as known to the Factorio mod portal
+        name: String,
+    },
+    /// Remove an installed mod by name
+    Remove {
+        /// Mod name as known to the Factorio mod portal
+        name: String,
+    },
+    /// Mark an installed mod as enabled in mods/mod-list.json
+    Enable {
+        /// Mod name as known to the Factorio mod portal
+        name: String,
+    },
+    /// Mark an installed mod as disabled in mods/mod-list.json
+    Disable {
+        /// Mod name as known to the Factorio mod portal
+        name: String,
+    },
+    /// List installed mods alongside the latest compatible release
+    List,
+    /// Delete the leftover `temp/` extraction directory
+    ClearCache,
+}
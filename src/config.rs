@@ -1,5 +1,6 @@
 use color_eyre::eyre::{Result, eyre};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 use tokio::fs;
 use toml::{Value, map::Map};
@@ -14,10 +15,17 @@ const ALLOWED_SECTION_KEYS: &[(&str, &[&str])] = &[
             "autoupdate-mods",
             "autoupdate-server",
             "autostart-when-finished",
+            "frozen",
+            "retry-attempts",
+            "max-concurrency",
         ],
     ),
 ];
 
+// Sections whose keys are user-defined (e.g. mod names) rather than a fixed set, so they
+// can't go through `sanitize_section`'s allow-list.
+const FREE_FORM_SECTIONS: &[&str] = &["mods"];
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct FactorioConfig {
     pub username: String,
@@ -32,6 +40,24 @@ pub struct ModManagerConfig {
     pub autoupdate_server: bool,
     #[serde(rename = "autostart-when-finished")]
     pub autostart_when_finished: bool,
+    /// When `true`, install exactly what's pinned in `mod-manager.lock` and verify already
+    /// installed mods against it instead of contacting the mods API.
+    #[serde(default)]
+    pub frozen: bool,
+    /// Maximum number of attempts for each mods-portal request before giving up.
+    #[serde(rename = "retry-attempts", default = "default_retry_attempts")]
+    pub retry_attempts: u32,
+    /// Maximum number of mods checked/updated concurrently during `check_mod_updates`.
+    #[serde(rename = "max-concurrency", default = "default_max_concurrency")]
+    pub max_concurrency: u32,
+}
+
+fn default_retry_attempts() -> u32 {
+    3
+}
+
+fn default_max_concurrency() -> u32 {
+    4
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -39,6 +65,10 @@ pub struct Config {
     pub factorio: FactorioConfig,
     #[serde(rename = "mod-manager")]
     pub mod_manager: ModManagerConfig,
+    /// Optional `[mods]` manifest: desired mod name -> semver requirement (e.g. `"*"` or
+    /// `">=0.6.0"`). Mods listed here but missing from `data/` are installed automatically.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mods: BTreeMap<String, String>,
 }
 
 impl Default for Config {
@@ -52,7 +82,11 @@ impl Default for Config {
                 autoupdate_mods: true,
                 autoupdate_server: true,
                 autostart_when_finished: true,
+                frozen: false,
+                retry_attempts: default_retry_attempts(),
+                max_concurrency: default_max_concurrency(),
             },
+            mods: BTreeMap::new(),
         }
     }
 }
@@ -100,6 +134,10 @@ async fn sanitize_existing(path: &Path) -> Result<Config> {
         };
         sanitize_section(table, section, allowed_keys, default_section);
     }
+    // Free-form sections don't have a fixed allow-list, just validate value types
+    for &section in FREE_FORM_SECTIONS {
+        sanitize_free_form_section(table, section);
+    }
 
     let new_toml = toml::to_string_pretty(&doc)?;
     fs::write(path, &new_toml).await?;
@@ -154,3 +192,34 @@ fn sanitize_section(
         }
     }
 }
+
+/// Sanitize a section with user-defined keys (e.g. `[mods]`): leave it absent if the user
+/// never added it, otherwise keep only string-valued entries (version requirements).
+fn sanitize_free_form_section(root: &mut Map<String, Value>, section: &str) {
+    let Some(entry) = root.get_mut(section) else {
+        return;
+    };
+    match entry {
+        Value::Table(map) => {
+            let invalid: Vec<_> = map
+                .iter()
+                .filter(|(_, v)| !matches!(v, Value::String(_)))
+                .map(|(k, _)| k.clone())
+                .collect();
+            for key in invalid {
+                warn!(
+                    "Removing '{}' from '[{}]', version requirement must be a string",
+                    key, section
+                );
+                map.remove(&key);
+            }
+        }
+        _ => {
+            warn!(
+                "'{}' was not a table (got {:?}), resetting to empty",
+                section, entry
+            );
+            *entry = Value::Table(Map::new());
+        }
+    }
+}
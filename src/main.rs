@@ -1,9 +1,12 @@
+use clap::Parser;
+use cli::Command;
 use color_eyre::eyre::{Result, bail, eyre};
 use osc8::Hyperlink;
 use std::path::Path;
 use tokio::fs;
 use tracing::info;
 
+mod cli;
 mod config;
 mod logging;
 mod updater;
@@ -14,6 +17,8 @@ async fn main() -> Result<()> {
     logging::init("info");
     info!("Starting factorio mod manager");
 
+    let args = cli::Cli::parse();
+
     let cfg = config::load_or_init(Path::new("mod-manager.toml")).await?;
 
     let docs_url = "https://wiki.factorio.com/Multiplayer#Dedicated/Headless_server";
@@ -35,11 +40,32 @@ async fn main() -> Result<()> {
     })
     .await?;
 
-    // If old temp directory exists, delete it
-    cleanup_temp_dir("temp").await;
+    let data_dir = Path::new("data");
 
-    // Check for mod updates
-    updater::check_update::check_mod_updates(Path::new("data"), &cfg).await?;
+    match args.command.unwrap_or(Command::Update) {
+        Command::Update => {
+            cleanup_temp_dir("temp").await;
+            updater::check_update::check_mod_updates(data_dir, &cfg).await?;
+        }
+        Command::Install { name } => {
+            updater::commands::install(data_dir, &cfg, &name).await?;
+        }
+        Command::Remove { name } => {
+            updater::commands::remove(data_dir, &name).await?;
+        }
+        Command::Enable { name } => {
+            updater::commands::set_enabled(data_dir, &name, true).await?;
+        }
+        Command::Disable { name } => {
+            updater::commands::set_enabled(data_dir, &name, false).await?;
+        }
+        Command::List => {
+            updater::commands::list(data_dir, &cfg).await?;
+        }
+        Command::ClearCache => {
+            cleanup_temp_dir("temp").await;
+        }
+    }
 
     info!("Terminating factorio mod manager");
     Ok(())
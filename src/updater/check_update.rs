@@ -1,17 +1,26 @@
 use crate::config::Config;
+use crate::updater::dependencies;
+use crate::updater::lockfile;
+use crate::updater::manifest;
+use crate::updater::mod_list;
 use crate::updater::mod_updater;
+use crate::updater::net;
 use color_eyre::eyre::Result;
+use futures::stream::{FuturesUnordered, StreamExt};
 use reqwest::Client;
-use semver::Version;
+use semver::{Version, VersionReq};
 use serde::Deserialize;
+use std::collections::VecDeque;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 use tracing::{debug, info, warn};
 
-#[derive(Deserialize)]
-struct LocalInfo {
-    name: String,
-    version: String,
+#[derive(Deserialize, Clone)]
+pub(crate) struct LocalInfo {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    #[serde(default)]
+    pub(crate) dependencies: Option<Vec<String>>,
 }
 
 #[derive(Deserialize)]
@@ -20,35 +29,81 @@ struct ApiResponse {
 }
 
 #[derive(Deserialize)]
-struct Release {
+pub(crate) struct Release {
+    pub(crate) version: String,
+    pub(crate) download_url: String,
+    pub(crate) file_name: String,
+    pub(crate) sha1: String,
+    pub(crate) info_json: ReleaseInfoJson,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ReleaseInfoJson {
+    pub(crate) factorio_version: String,
+}
+
+#[derive(Deserialize)]
+struct BaseInfo {
     version: String,
-    download_url: String,
-    file_name: String,
-    sha1: String,
 }
 
 pub async fn check_mod_updates(data_dir: &Path, cfg: &Config) -> Result<()> {
     info!("Starting mod update check in {:?}", data_dir);
 
+    let engine_version = read_engine_version(data_dir).await?;
+    info!("Detected Factorio engine version {}", engine_version);
+
+    if cfg.mod_manager.frozen {
+        return lockfile::install_frozen(data_dir, cfg).await;
+    }
+
     let client = Client::new();
+    let mut queue: VecDeque<PathBuf> = VecDeque::new();
     let mut dir = fs::read_dir(data_dir).await?;
-
     while let Some(entry) = dir.next_entry().await? {
         let path = entry.path();
-        if !should_process_mod(&path).await {
-            continue;
+        if should_process_mod(&path).await {
+            queue.push_back(path);
+        }
+    }
+
+    let max_concurrency = cfg.mod_manager.max_concurrency.max(1) as usize;
+    let mut tasks = FuturesUnordered::new();
+    while tasks.len() < max_concurrency {
+        match queue.pop_front() {
+            Some(path) => tasks.push(process_mod_dir_tracked(path, &client, cfg, &engine_version)),
+            None => break,
+        }
+    }
+    while let Some((path, result)) = tasks.next().await {
+        if let Err(e) = result {
+            warn!("Failed to process mod at {:?}: {:?}", path, e);
         }
-        if let Err(e) = process_mod_dir(path, &client, cfg).await {
-            warn!("Failed to process mod at {:?}: {:?}", entry.path(), e);
+        if let Some(path) = queue.pop_front() {
+            tasks.push(process_mod_dir_tracked(path, &client, cfg, &engine_version));
         }
     }
 
+    manifest::install_missing(data_dir, &client, cfg, &engine_version).await?;
+    dependencies::resolve_and_install(data_dir, &client, cfg, &engine_version).await?;
+    mod_list::sync_installed(data_dir).await?;
+
     info!("Mod update check complete");
     Ok(())
 }
 
+/// Read the running Factorio engine's `major.minor` version from `data/base/info.json`.
+pub(crate) async fn read_engine_version(data_dir: &Path) -> Result<String> {
+    let info_path = data_dir.join("base").join("info.json");
+    debug!("Reading engine version from {:?}", info_path);
+    let data = fs::read_to_string(&info_path).await?;
+    let info: BaseInfo = serde_json::from_str(&data)?;
+    let version = Version::parse(&info.version)?;
+    Ok(format!("{}.{}", version.major, version.minor))
+}
+
 /// Decide whether this entry is a mod directory we care about.
-async fn should_process_mod(path: &Path) -> bool {
+pub(crate) async fn should_process_mod(path: &Path) -> bool {
     let name = path
         .file_name()
         .and_then(|s| s.to_str())
@@ -65,24 +120,62 @@ async fn should_process_mod(path: &Path) -> bool {
     !skip
 }
 
+/// Run [`process_mod_dir`], carrying `path` alongside its result so a bounded worker pool
+/// can still name which mod failed after the future has been consumed.
+async fn process_mod_dir_tracked(
+    path: PathBuf,
+    client: &Client,
+    cfg: &Config,
+    engine_version: &str,
+) -> (PathBuf, Result<()>) {
+    let result = process_mod_dir(path.clone(), client, cfg, engine_version).await;
+    (path, result)
+}
+
 /// Process one mod folder: read local info, fetch remote, and update if needed.
-async fn process_mod_dir(path: PathBuf, client: &Client, cfg: &Config) -> Result<()> {
+async fn process_mod_dir(
+    path: PathBuf,
+    client: &Client,
+    cfg: &Config,
+    engine_version: &str,
+) -> Result<()> {
     let local = read_local_info(&path).await?;
     info!("Local mod '{}' version: {}", local.name, local.version);
 
-    let releases = fetch_remote_releases(&local.name, client).await?;
+    let releases = fetch_remote_releases(&local.name, client, cfg.mod_manager.retry_attempts).await?;
     debug!("Found {} releases for '{}'", releases.len(), local.name);
 
-    if let Some((latest_ver, rel)) = pick_latest(&releases) {
+    let req = manifest::requirement_for(cfg, &local.name)?;
+    if let Some((latest_ver, rel)) = pick_release_matching(&releases, req.as_ref(), engine_version) {
         compare_and_update(&local, latest_ver, rel, cfg).await?;
     } else {
-        warn!("No valid releases found for '{}'", local.name);
+        warn!(
+            "No release of '{}' compatible with Factorio {} found",
+            local.name, engine_version
+        );
     }
     Ok(())
 }
 
+/// Find an installed mod's folder by the `name` field in its `info.json`, not its folder
+/// name.
+pub(crate) async fn find_mod_path(data_dir: &Path, name: &str) -> Result<Option<PathBuf>> {
+    let mut rd = fs::read_dir(data_dir).await?;
+    while let Some(entry) = rd.next_entry().await? {
+        let path = entry.path();
+        if !should_process_mod(&path).await {
+            continue;
+        }
+        let info = read_local_info(&path).await?;
+        if info.name == name {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
 /// Read and parse `info.json` in the mod folder.
-async fn read_local_info(path: &Path) -> Result<LocalInfo> {
+pub(crate) async fn read_local_info(path: &Path) -> Result<LocalInfo> {
     let info_path = path.join("info.json");
     debug!("Reading local info from {:?}", info_path);
     let data = fs::read_to_string(&info_path).await?;
@@ -90,19 +183,31 @@ async fn read_local_info(path: &Path) -> Result<LocalInfo> {
     Ok(info)
 }
 
-/// Fetch the full list of releases from the Factorio mods API.
-async fn fetch_remote_releases(name: &str, client: &Client) -> Result<Vec<Release>> {
+/// Fetch the full list of releases from the Factorio mods API, retrying transient failures.
+pub(crate) async fn fetch_remote_releases(
+    name: &str,
+    client: &Client,
+    retries: u32,
+) -> Result<Vec<Release>> {
     let url = format!("https://mods.factorio.com/api/mods/{}/full", name);
     info!("Fetching remote metadata from {}", url);
-    let resp: ApiResponse = client.get(&url).send().await?.json().await?;
-    Ok(resp.releases)
+    let resp = net::retry(retries, || client.get(&url).send()).await?;
+    let body: ApiResponse = resp.json().await?;
+    Ok(body.releases)
 }
 
-/// Pick the highest valid semver release.
-fn pick_latest(releases: &[Release]) -> Option<(Version, &Release)> {
+/// Pick the highest release satisfying `req` and compatible with `engine_version`
+/// (`major.minor`), or the highest compatible release overall if `req` is `None`.
+pub(crate) fn pick_release_matching<'a>(
+    releases: &'a [Release],
+    req: Option<&VersionReq>,
+    engine_version: &str,
+) -> Option<(Version, &'a Release)> {
     releases
         .iter()
+        .filter(|r| r.info_json.factorio_version == engine_version)
         .filter_map(|r| Version::parse(&r.version).ok().map(|v| (v, r)))
+        .filter(|(v, _)| req.map_or(true, |req| req.matches(v)))
         .max_by(|(v1, _), (v2, _)| v1.cmp(v2))
 }
 
@@ -128,14 +233,19 @@ async fn compare_and_update(
             "Updating '{}' from {} â†’ {}",
             local.name, local.version, rel.version
         );
-        mod_updater::update_mod(
+        let dest = mod_updater::update_mod(
             &rel.file_name,
             &rel.download_url,
             &rel.sha1,
             &cfg.factorio.username,
             &cfg.factorio.token,
+            cfg.mod_manager.retry_attempts,
         )
         .await?;
+        let content_hash = mod_updater::hash_dir_contents(&dest).await?;
+        lockfile::record(&local.name, &rel.version, &rel.download_url, &rel.sha1, &content_hash)
+            .await?;
+        mod_list::set_enabled(&local.name, true).await?;
         info!("Successfully updated '{}'", local.name);
     }
     Ok(())
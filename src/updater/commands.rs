@@ -0,0 +1,104 @@
+use crate::config::Config;
+use crate::updater::{check_update, lockfile, mod_list, mod_updater};
+use color_eyre::eyre::{Result, eyre};
+use reqwest::Client;
+use std::path::Path;
+use tokio::fs;
+use tracing::info;
+
+/// Fetch and install the Factorio-version-compatible release of `name`, regardless of
+/// whether it is already present under `data_dir`.
+pub async fn install(data_dir: &Path, cfg: &Config, name: &str) -> Result<()> {
+    let engine_version = check_update::read_engine_version(data_dir).await?;
+    let client = Client::new();
+    let releases =
+        check_update::fetch_remote_releases(name, &client, cfg.mod_manager.retry_attempts).await?;
+    let (_, release) = check_update::pick_release_matching(&releases, None, &engine_version)
+        .ok_or_else(|| {
+            eyre!(
+                "no release of '{}' compatible with Factorio {}",
+                name,
+                engine_version
+            )
+        })?;
+
+    let dest = mod_updater::update_mod(
+        &release.file_name,
+        &release.download_url,
+        &release.sha1,
+        &cfg.factorio.username,
+        &cfg.factorio.token,
+        cfg.mod_manager.retry_attempts,
+    )
+    .await?;
+    let content_hash = mod_updater::hash_dir_contents(&dest).await?;
+    lockfile::record(
+        name,
+        &release.version,
+        &release.download_url,
+        &release.sha1,
+        &content_hash,
+    )
+    .await?;
+    mod_list::set_enabled(name, true).await?;
+    info!("Installed '{}' {}", name, release.version);
+    Ok(())
+}
+
+/// Remove an installed mod's folder from `data_dir`.
+pub async fn remove(data_dir: &Path, name: &str) -> Result<()> {
+    let path = check_update::find_mod_path(data_dir, name)
+        .await?
+        .ok_or_else(|| eyre!("'{}' is not installed", name))?;
+    info!("Removing '{}' from {:?}", name, path);
+    fs::remove_dir_all(&path).await?;
+    mod_list::remove_entry(name).await?;
+    Ok(())
+}
+
+/// Print every installed mod with its local version and the latest Factorio-compatible
+/// release available.
+pub async fn list(data_dir: &Path, cfg: &Config) -> Result<()> {
+    let engine_version = check_update::read_engine_version(data_dir).await?;
+    let client = Client::new();
+
+    let mut rd = fs::read_dir(data_dir).await?;
+    while let Some(entry) = rd.next_entry().await? {
+        let path = entry.path();
+        if !check_update::should_process_mod(&path).await {
+            continue;
+        }
+
+        let local = check_update::read_local_info(&path).await?;
+        let releases = check_update::fetch_remote_releases(
+            &local.name,
+            &client,
+            cfg.mod_manager.retry_attempts,
+        )
+        .await?;
+        match check_update::pick_release_matching(&releases, None, &engine_version) {
+            Some((latest, _)) => {
+                println!("{}: {} (latest: {})", local.name, local.version, latest)
+            }
+            None => println!(
+                "{}: {} (no release compatible with Factorio {})",
+                local.name, local.version, engine_version
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Mark an installed mod as enabled or disabled in `mods/mod-list.json`.
+pub async fn set_enabled(data_dir: &Path, name: &str, enabled: bool) -> Result<()> {
+    check_update::find_mod_path(data_dir, name)
+        .await?
+        .ok_or_else(|| eyre!("'{}' is not installed", name))?;
+    mod_list::set_enabled(name, enabled).await?;
+    info!(
+        "{} '{}' in mod-list.json",
+        if enabled { "Enabled" } else { "Disabled" },
+        name
+    );
+    Ok(())
+}
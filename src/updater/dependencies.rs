@@ -0,0 +1,307 @@
+use crate::config::Config;
+use crate::updater::check_update::{self, LocalInfo};
+use crate::updater::lockfile;
+use crate::updater::mod_list;
+use crate::updater::mod_updater;
+use color_eyre::eyre::{Result, bail, eyre};
+use reqwest::Client;
+use semver::{Version, VersionReq};
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
+use tokio::fs;
+use tracing::{debug, info, warn};
+
+/// The effect of one entry in a mod's `dependencies` array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DependencyKind {
+    /// No prefix: must be installed and loaded before this mod.
+    Required,
+    /// `~`: must be installed, but does not affect load order.
+    NoLoadOrder,
+    /// `?`: installed only if the user already has it.
+    Optional,
+    /// `(?)`: like `Optional`, but hidden from the dependency list in-game.
+    HiddenOptional,
+    /// `!`: must not be installed alongside this mod.
+    Incompatible,
+}
+
+/// One parsed entry from a mod's `dependencies` array, e.g. `">= 1.2.0"` style constraints
+/// on `<mod-name>`.
+#[derive(Debug, Clone)]
+pub(crate) struct Dependency {
+    pub(crate) kind: DependencyKind,
+    pub(crate) name: String,
+    pub(crate) version_req: Option<VersionReq>,
+}
+
+/// Parse a single Factorio dependency string, e.g. `"! incompatible-mod"` or
+/// `"some-mod >= 1.2.0"`.
+fn parse_dependency(raw: &str) -> Result<Dependency> {
+    let trimmed = raw.trim();
+    let (kind, rest) = if let Some(rest) = trimmed.strip_prefix("(?)") {
+        (DependencyKind::HiddenOptional, rest)
+    } else if let Some(rest) = trimmed.strip_prefix('!') {
+        (DependencyKind::Incompatible, rest)
+    } else if let Some(rest) = trimmed.strip_prefix('?') {
+        (DependencyKind::Optional, rest)
+    } else if let Some(rest) = trimmed.strip_prefix('~') {
+        (DependencyKind::NoLoadOrder, rest)
+    } else {
+        (DependencyKind::Required, trimmed)
+    };
+
+    let mut parts = rest.trim().split_whitespace();
+    let name = parts
+        .next()
+        .ok_or_else(|| eyre!("empty dependency string `{}`", raw))?
+        .to_string();
+    let version_req = match (parts.next(), parts.next()) {
+        (Some(op), Some(version)) => Some(
+            VersionReq::parse(&format!("{op}{version}"))
+                .map_err(|e| eyre!("invalid version requirement in `{}`: {}", raw, e))?,
+        ),
+        _ => None,
+    };
+
+    Ok(Dependency {
+        kind,
+        name,
+        version_req,
+    })
+}
+
+fn parse_dependencies(info: &LocalInfo) -> Result<Vec<Dependency>> {
+    info.dependencies
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .map(|raw| parse_dependency(raw))
+        .collect()
+}
+
+/// Walk every installed mod's `dependencies`, install anything required that is missing,
+/// and bail if an installed combination violates an `!`-incompatibility or version
+/// constraint.
+pub(crate) async fn resolve_and_install(
+    data_dir: &Path,
+    client: &Client,
+    cfg: &Config,
+    engine_version: &str,
+) -> Result<()> {
+    info!("Resolving mod dependencies in {:?}", data_dir);
+
+    let mut queue: VecDeque<Dependency> = VecDeque::new();
+    for info in collect_installed(data_dir).await? {
+        queue.extend(parse_dependencies(&info)?);
+    }
+
+    // `!`-incompatibilities are only meaningful once every required dependency has been
+    // resolved, since a conflicting mod can be installed by a later queue entry after the
+    // incompatibility was declared. Collect them here and re-check against the final
+    // installed set once the queue has fully drained, instead of bailing based on whatever
+    // happens to be installed at the moment the entry is dequeued.
+    let mut incompatibilities: Vec<Dependency> = Vec::new();
+
+    let mut attempted: HashSet<String> = HashSet::new();
+    while let Some(dep) = queue.pop_front() {
+        if dep.name == "base" || dep.name == "core" {
+            continue;
+        }
+
+        match dep.kind {
+            DependencyKind::Incompatible => {
+                incompatibilities.push(dep);
+            }
+            DependencyKind::Optional | DependencyKind::HiddenOptional => {
+                if let Some(info) = find_installed(data_dir, &dep.name).await? {
+                    check_requirement(&dep, &Version::parse(&info.version)?)?;
+                }
+            }
+            DependencyKind::Required | DependencyKind::NoLoadOrder => {
+                if let Some(info) = find_installed(data_dir, &dep.name).await? {
+                    check_requirement(&dep, &Version::parse(&info.version)?)?;
+                    continue;
+                }
+                if !attempted.insert(dep.name.clone()) {
+                    continue;
+                }
+                let installed_info =
+                    install_dependency(data_dir, client, cfg, &dep, engine_version).await?;
+                queue.extend(parse_dependencies(&installed_info)?);
+            }
+        }
+    }
+
+    for dep in &incompatibilities {
+        if find_installed(data_dir, &dep.name).await?.is_some() {
+            bail!(
+                "`{}` is installed but is marked incompatible by another mod",
+                dep.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn check_requirement(dep: &Dependency, installed_version: &Version) -> Result<()> {
+    if let Some(req) = &dep.version_req {
+        if !req.matches(installed_version) {
+            bail!(
+                "installed `{}` {} does not satisfy required {}",
+                dep.name,
+                installed_version,
+                req
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Fetch, verify and extract the mod satisfying `dep`, returning its freshly-written
+/// `info.json`.
+async fn install_dependency(
+    data_dir: &Path,
+    client: &Client,
+    cfg: &Config,
+    dep: &Dependency,
+    engine_version: &str,
+) -> Result<LocalInfo> {
+    info!("Installing missing dependency `{}`", dep.name);
+    let releases =
+        check_update::fetch_remote_releases(&dep.name, client, cfg.mod_manager.retry_attempts)
+            .await?;
+    let (_, release) =
+        check_update::pick_release_matching(&releases, dep.version_req.as_ref(), engine_version)
+            .ok_or_else(|| {
+                eyre!(
+                    "no release of `{}` compatible with Factorio {} satisfies {}",
+                    dep.name,
+                    engine_version,
+                    dep.version_req
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_else(|| "*".to_string())
+                )
+            })?;
+
+    let dest = mod_updater::update_mod(
+        &release.file_name,
+        &release.download_url,
+        &release.sha1,
+        &cfg.factorio.username,
+        &cfg.factorio.token,
+        cfg.mod_manager.retry_attempts,
+    )
+    .await?;
+    let content_hash = mod_updater::hash_dir_contents(&dest).await?;
+    lockfile::record(
+        &dep.name,
+        &release.version,
+        &release.download_url,
+        &release.sha1,
+        &content_hash,
+    )
+    .await?;
+    mod_list::set_enabled(&dep.name, true).await?;
+
+    find_installed(data_dir, &dep.name)
+        .await?
+        .ok_or_else(|| eyre!("`{}` was installed but is not visible in `data/`", dep.name))
+}
+
+/// Read the `info.json` of every mod folder in `data_dir`, skipping `base`/`core`.
+async fn collect_installed(data_dir: &Path) -> Result<Vec<LocalInfo>> {
+    let mut installed = Vec::new();
+    let mut rd = fs::read_dir(data_dir).await?;
+    while let Some(entry) = rd.next_entry().await? {
+        let path = entry.path();
+        if !fs::metadata(&path).await?.is_dir() {
+            continue;
+        }
+        let folder_name = entry.file_name().to_string_lossy().to_string();
+        if folder_name == "base" || folder_name == "core" {
+            continue;
+        }
+        if fs::metadata(path.join("info.json")).await.is_err() {
+            continue;
+        }
+        match check_update::read_local_info(&path).await {
+            Ok(info) => installed.push(info),
+            Err(e) => warn!("Skipping {:?}, could not read info.json: {:?}", path, e),
+        }
+    }
+    Ok(installed)
+}
+
+/// Find an installed mod by the `name` field in its `info.json`, not its folder name.
+pub(crate) async fn find_installed(data_dir: &Path, name: &str) -> Result<Option<LocalInfo>> {
+    for info in collect_installed(data_dir).await? {
+        if info.name == name {
+            debug!("Found `{}` already installed", name);
+            return Ok(Some(info));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dependency_required() {
+        let dep = parse_dependency("some-mod >= 1.2.0").unwrap();
+        assert_eq!(dep.kind, DependencyKind::Required);
+        assert_eq!(dep.name, "some-mod");
+        assert!(dep.version_req.unwrap().matches(&Version::parse("1.2.0").unwrap()));
+    }
+
+    #[test]
+    fn parse_dependency_no_load_order() {
+        let dep = parse_dependency("~ some-mod").unwrap();
+        assert_eq!(dep.kind, DependencyKind::NoLoadOrder);
+        assert_eq!(dep.name, "some-mod");
+        assert!(dep.version_req.is_none());
+    }
+
+    #[test]
+    fn parse_dependency_optional() {
+        let dep = parse_dependency("? some-mod").unwrap();
+        assert_eq!(dep.kind, DependencyKind::Optional);
+        assert_eq!(dep.name, "some-mod");
+    }
+
+    #[test]
+    fn parse_dependency_hidden_optional() {
+        let dep = parse_dependency("(?) some-mod >= 1.0.0").unwrap();
+        assert_eq!(dep.kind, DependencyKind::HiddenOptional);
+        assert_eq!(dep.name, "some-mod");
+        assert!(dep.version_req.unwrap().matches(&Version::parse("1.0.0").unwrap()));
+    }
+
+    #[test]
+    fn parse_dependency_incompatible() {
+        let dep = parse_dependency("! some-mod").unwrap();
+        assert_eq!(dep.kind, DependencyKind::Incompatible);
+        assert_eq!(dep.name, "some-mod");
+    }
+
+    #[test]
+    fn parse_dependency_rejects_empty() {
+        assert!(parse_dependency("   ").is_err());
+    }
+
+    #[test]
+    fn check_requirement_passes_when_satisfied() {
+        let dep = parse_dependency("some-mod >= 1.0.0").unwrap();
+        assert!(check_requirement(&dep, &Version::parse("1.2.0").unwrap()).is_ok());
+    }
+
+    #[test]
+    fn check_requirement_bails_when_unsatisfied() {
+        let dep = parse_dependency("some-mod >= 2.0.0").unwrap();
+        assert!(check_requirement(&dep, &Version::parse("1.2.0").unwrap()).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::config::Config;
+use crate::updater::check_update;
+use crate::updater::mod_list;
+use crate::updater::mod_updater;
+use color_eyre::eyre::{Result, bail, eyre};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use tokio::fs;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+const LOCK_PATH: &str = "mod-manager.lock";
+
+/// Serializes load-mutate-save round trips against `mod-manager.lock`, so concurrent
+/// `record` calls from the bounded worker pool in `check_update` don't clobber each other.
+static LOCK_FILE_MUTEX: OnceLock<Mutex<()>> = OnceLock::new();
+
+fn lock_file_mutex() -> &'static Mutex<()> {
+    LOCK_FILE_MUTEX.get_or_init(|| Mutex::new(()))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct LockedMod {
+    pub(crate) version: String,
+    pub(crate) download_url: String,
+    pub(crate) sha1: String,
+    /// Content hash of the extracted mod folder at install time (see
+    /// [`mod_updater::hash_dir_contents`]), used to detect drift in an already-present
+    /// folder without needing to keep the original archive around. Empty for entries
+    /// written by older versions of this tool, before this field existed.
+    #[serde(default)]
+    pub(crate) content_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct Lockfile {
+    #[serde(default)]
+    pub(crate) mods: BTreeMap<String, LockedMod>,
+}
+
+/// Load `mod-manager.lock`, or an empty lockfile if it doesn't exist yet.
+pub(crate) async fn load() -> Result<Lockfile> {
+    let path = Path::new(LOCK_PATH);
+    if fs::metadata(path).await.is_err() {
+        return Ok(Lockfile::default());
+    }
+    let contents = fs::read_to_string(path).await?;
+    toml::from_str(&contents).map_err(|e| eyre!("invalid lockfile at {:?}: {}", path, e))
+}
+
+async fn save(lock: &Lockfile) -> Result<()> {
+    let toml_str = toml::to_string_pretty(lock)?;
+    fs::write(LOCK_PATH, &toml_str).await?;
+    Ok(())
+}
+
+/// Record (or update) the resolved install details for `name` in `mod-manager.lock`.
+pub(crate) async fn record(
+    name: &str,
+    version: &str,
+    download_url: &str,
+    sha1: &str,
+    content_hash: &str,
+) -> Result<()> {
+    let _guard = lock_file_mutex().lock().await;
+    let mut lock = load().await?;
+    lock.mods.insert(
+        name.to_string(),
+        LockedMod {
+            version: version.to_string(),
+            download_url: download_url.to_string(),
+            sha1: sha1.to_string(),
+            content_hash: content_hash.to_string(),
+        },
+    );
+    save(&lock).await?;
+    debug!("Recorded lock entry for '{}'", name);
+    Ok(())
+}
+
+/// In frozen mode, install exactly what's pinned in `mod-manager.lock` without contacting
+/// the mods API, and verify each already-installed folder's contents against the pinned
+/// `content_hash` rather than trusting the `info.json` version string.
+pub(crate) async fn install_frozen(data_dir: &Path, cfg: &Config) -> Result<()> {
+    info!("Frozen mode: installing from {}", LOCK_PATH);
+    let lock = load().await?;
+    if lock.mods.is_empty() {
+        warn!(
+            "frozen = true but {} has no entries, nothing to do",
+            LOCK_PATH
+        );
+        return Ok(());
+    }
+
+    for (name, locked) in &lock.mods {
+        match check_update::find_mod_path(data_dir, name).await? {
+            Some(path) => {
+                if locked.content_hash.is_empty() {
+                    warn!(
+                        "'{}' has no content hash in {} (written by an older version), skipping drift check",
+                        name, LOCK_PATH
+                    );
+                    continue;
+                }
+                let actual_hash = mod_updater::hash_dir_contents(&path).await?;
+                if actual_hash == locked.content_hash {
+                    debug!("'{}' matches locked content hash", name);
+                } else {
+                    bail!(
+                        "drift detected: '{}' contents do not match the hash pinned in {}",
+                        name,
+                        LOCK_PATH
+                    );
+                }
+            }
+            None => {
+                info!("Installing '{}' {} from lockfile", name, locked.version);
+                mod_updater::update_mod(
+                    name,
+                    &locked.download_url,
+                    &locked.sha1,
+                    &cfg.factorio.username,
+                    &cfg.factorio.token,
+                    cfg.mod_manager.retry_attempts,
+                )
+                .await?;
+                mod_list::set_enabled(name, true).await?;
+            }
+        }
+    }
+
+    mod_list::sync_installed(data_dir).await?;
+    info!("Frozen install complete");
+    Ok(())
+}
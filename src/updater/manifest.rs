@@ -0,0 +1,94 @@
+use crate::config::Config;
+use crate::updater::check_update;
+use crate::updater::dependencies;
+use crate::updater::lockfile;
+use crate::updater::mod_list;
+use crate::updater::mod_updater;
+use color_eyre::eyre::{Result, eyre};
+use reqwest::Client;
+use semver::VersionReq;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Parse a `[mods]` manifest entry's version requirement, e.g. `"*"` or `">=0.6.0"`.
+fn parse_requirement(name: &str, raw: &str) -> Result<VersionReq> {
+    VersionReq::parse(raw)
+        .map_err(|e| eyre!("invalid version requirement `{}` for mod `{}`: {}", raw, name, e))
+}
+
+/// The version requirement the `[mods]` manifest places on `name`, if any.
+pub(crate) fn requirement_for(cfg: &Config, name: &str) -> Result<Option<VersionReq>> {
+    cfg.mods
+        .get(name)
+        .map(|raw| parse_requirement(name, raw))
+        .transpose()
+}
+
+/// Install every manifest entry that has no corresponding folder under `data_dir` yet.
+/// Entries that already exist are left for `check_mod_updates`'s normal update pass, which
+/// consults [`requirement_for`] to honor the same version constraint.
+///
+/// A bad entry (renamed mod, unsatisfiable constraint, flaky download, ...) is logged as a
+/// warning rather than aborting the whole run, so one broken manifest entry doesn't block
+/// updates for every other already-working mod in the pack.
+pub(crate) async fn install_missing(
+    data_dir: &Path,
+    client: &Client,
+    cfg: &Config,
+    engine_version: &str,
+) -> Result<()> {
+    for (name, raw_req) in &cfg.mods {
+        if dependencies::find_installed(data_dir, name).await?.is_some() {
+            continue;
+        }
+
+        if let Err(e) = install_entry(client, cfg, engine_version, name, raw_req).await {
+            warn!("Failed to install manifest entry `{}`: {:?}", name, e);
+        }
+    }
+    Ok(())
+}
+
+/// Fetch, verify and install a single `[mods]` manifest entry that's missing from `data_dir`.
+async fn install_entry(
+    client: &Client,
+    cfg: &Config,
+    engine_version: &str,
+    name: &str,
+    raw_req: &str,
+) -> Result<()> {
+    info!("Manifest declares `{}` which is not installed, fetching", name);
+    let req = parse_requirement(name, raw_req)?;
+    let releases =
+        check_update::fetch_remote_releases(name, client, cfg.mod_manager.retry_attempts).await?;
+    let (_, release) = check_update::pick_release_matching(&releases, Some(&req), engine_version)
+        .ok_or_else(|| {
+            eyre!(
+                "no release of `{}` compatible with Factorio {} satisfies {}",
+                name,
+                engine_version,
+                raw_req
+            )
+        })?;
+
+    let dest = mod_updater::update_mod(
+        &release.file_name,
+        &release.download_url,
+        &release.sha1,
+        &cfg.factorio.username,
+        &cfg.factorio.token,
+        cfg.mod_manager.retry_attempts,
+    )
+    .await?;
+    let content_hash = mod_updater::hash_dir_contents(&dest).await?;
+    lockfile::record(
+        name,
+        &release.version,
+        &release.download_url,
+        &release.sha1,
+        &content_hash,
+    )
+    .await?;
+    mod_list::set_enabled(name, true).await?;
+    Ok(())
+}
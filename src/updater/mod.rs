@@ -0,0 +1,8 @@
+pub mod check_update;
+pub mod commands;
+pub mod dependencies;
+pub mod lockfile;
+pub mod manifest;
+pub mod mod_list;
+pub mod mod_updater;
+pub(crate) mod net;
@@ -0,0 +1,98 @@
+use crate::updater::check_update;
+use color_eyre::eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::OnceLock;
+use tokio::fs;
+use tokio::sync::Mutex;
+use tracing::info;
+
+const MOD_LIST_PATH: &str = "mods/mod-list.json";
+
+/// Serializes load-mutate-save round trips against `mod-list.json`, so concurrent callers
+/// (e.g. the bounded worker pool in `check_update`) don't clobber each other's writes.
+static MOD_LIST_MUTEX: OnceLock<Mutex<()>> = OnceLock::new();
+
+fn mod_list_mutex() -> &'static Mutex<()> {
+    MOD_LIST_MUTEX.get_or_init(|| Mutex::new(()))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ModListEntry {
+    pub(crate) name: String,
+    pub(crate) enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct ModList {
+    #[serde(default)]
+    pub(crate) mods: Vec<ModListEntry>,
+}
+
+/// Load `mods/mod-list.json`, or an empty list if it doesn't exist yet.
+pub(crate) async fn load() -> Result<ModList> {
+    if fs::metadata(MOD_LIST_PATH).await.is_err() {
+        return Ok(ModList::default());
+    }
+    let data = fs::read_to_string(MOD_LIST_PATH).await?;
+    serde_json::from_str(&data).map_err(|e| eyre!("invalid {}: {}", MOD_LIST_PATH, e))
+}
+
+async fn save(list: &ModList) -> Result<()> {
+    if let Some(parent) = Path::new(MOD_LIST_PATH).parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    let json = serde_json::to_string_pretty(list)?;
+    fs::write(MOD_LIST_PATH, json).await?;
+    Ok(())
+}
+
+/// Set (creating if absent) whether `name` is enabled in `mod-list.json`.
+pub(crate) async fn set_enabled(name: &str, enabled: bool) -> Result<()> {
+    let _guard = mod_list_mutex().lock().await;
+    let mut list = load().await?;
+    match list.mods.iter_mut().find(|m| m.name == name) {
+        Some(entry) => entry.enabled = enabled,
+        None => list.mods.push(ModListEntry {
+            name: name.to_string(),
+            enabled,
+        }),
+    }
+    save(&list).await?;
+    info!("Set '{}' enabled={} in {}", name, enabled, MOD_LIST_PATH);
+    Ok(())
+}
+
+/// Drop `name`'s entry entirely, e.g. after the mod is removed.
+pub(crate) async fn remove_entry(name: &str) -> Result<()> {
+    let _guard = mod_list_mutex().lock().await;
+    let mut list = load().await?;
+    list.mods.retain(|m| m.name != name);
+    save(&list).await?;
+    info!("Removed '{}' from {}", name, MOD_LIST_PATH);
+    Ok(())
+}
+
+/// Ensure every mod installed under `data_dir` has a `mod-list.json` entry, enabled by
+/// default if it's new.
+pub(crate) async fn sync_installed(data_dir: &Path) -> Result<()> {
+    let _guard = mod_list_mutex().lock().await;
+    let mut list = load().await?;
+    let mut rd = fs::read_dir(data_dir).await?;
+    while let Some(entry) = rd.next_entry().await? {
+        let path = entry.path();
+        if !check_update::should_process_mod(&path).await {
+            continue;
+        }
+        let info = check_update::read_local_info(&path).await?;
+        if !list.mods.iter().any(|m| m.name == info.name) {
+            info!("Adding new {} entry for '{}'", MOD_LIST_PATH, info.name);
+            list.mods.push(ModListEntry {
+                name: info.name,
+                enabled: true,
+            });
+        }
+    }
+    save(&list).await?;
+    Ok(())
+}
@@ -1,20 +1,24 @@
+use crate::updater::net;
 use color_eyre::eyre::{Result, eyre};
-use reqwest::Url;
+use rand::Rng;
+use reqwest::{Client, Url};
 use sha1::{Digest, Sha1};
 use std::io::Cursor;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use tracing::{debug, error, info};
 use walkdir::WalkDir;
 use zip::ZipArchive;
 
+/// Download, verify and extract `name`, returning the directory it was installed into.
 pub async fn update_mod(
     name: &str,
     download_url: &str,
     expected_sha: &str,
     username: &str,
     token: &str,
-) -> Result<()> {
+    retries: u32,
+) -> Result<PathBuf> {
     info!("Beginning update for mod '{}'", name);
 
     // Ensure the data/ directory exists
@@ -39,8 +43,9 @@ pub async fn update_mod(
     }
     info!("Downloading '{}' from {}", name, url);
 
-    // Download ZIP into memory
-    let resp = reqwest::get(url.clone()).await?;
+    // Download ZIP into memory, retrying transient failures
+    let client = Client::new();
+    let resp = net::retry(retries, || client.get(url.clone()).send()).await?;
     if !resp.status().is_success() {
         error!(
             "Failed to download '{}': HTTP {} at {}",
@@ -77,25 +82,23 @@ pub async fn update_mod(
     }
     info!("SHA1 verified for '{}'", name);
 
-    // In-memory unzip into temp/
-    let temp_dir = Path::new("temp");
-    if temp_dir.exists() {
-        info!("Cleaning up existing temp directory at {:?}", temp_dir);
-        fs::remove_dir_all(temp_dir).await?;
-    }
-    fs::create_dir_all(temp_dir).await?;
+    // In-memory unzip into a temp directory unique to this call, so concurrent updates
+    // (the bounded worker pool in `check_update`) never share, truncate, or race-delete
+    // each other's extraction area.
+    let temp_dir = unique_temp_dir(name);
+    fs::create_dir_all(&temp_dir).await?;
     info!("Extracting archive into {:?}", temp_dir);
 
     let reader = Cursor::new(bytes);
     let mut archive =
         ZipArchive::new(reader).map_err(|e| eyre!("Failed to read ZIP for {}: {}", name, e))?;
     archive
-        .extract(temp_dir)
+        .extract(&temp_dir)
         .map_err(|e| eyre!("Failed to extract {}: {}", name, e))?;
     info!("Extraction complete for '{}'", name);
 
     // Find the folder that contains info.json
-    let extracted_root = WalkDir::new(temp_dir)
+    let extracted_root = WalkDir::new(&temp_dir)
         .into_iter()
         .filter_map(|e| e.ok())
         .find(|e| e.file_name() == "info.json")
@@ -143,5 +146,37 @@ pub async fn update_mod(
     fs::remove_dir_all(temp_dir).await?;
 
     info!("Mod '{}' updated successfully â†’ {}", name, neat);
-    Ok(())
+    Ok(dest)
+}
+
+/// Build a temp directory path unique to this call, so concurrent `update_mod` invocations
+/// never share extraction state.
+fn unique_temp_dir(name: &str) -> PathBuf {
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let suffix: u64 = rand::thread_rng().gen();
+    Path::new("temp").join(format!("{}-{:016x}", slug, suffix))
+}
+
+/// Compute a deterministic content hash over every file under `dir` (relative path plus
+/// bytes), used to detect drift in an already-installed mod folder without retaining the
+/// original archive.
+pub async fn hash_dir_contents(dir: &Path) -> Result<String> {
+    let mut paths: Vec<_> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.path().to_path_buf())
+        .collect();
+    paths.sort();
+
+    let mut hasher = Sha1::new();
+    for path in paths {
+        let rel = path.strip_prefix(dir).unwrap_or(&path);
+        hasher.update(rel.to_string_lossy().as_bytes());
+        hasher.update(fs::read(&path).await?);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
 }
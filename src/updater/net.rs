@@ -0,0 +1,51 @@
+use color_eyre::eyre::{Result, eyre};
+use rand::Rng;
+use reqwest::Response;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+/// Retry an HTTP call up to `attempts` times, retrying on connection errors, timeouts, and
+/// 5xx responses, with exponential backoff plus jitter between attempts. Surfaces the final
+/// failure as a `color_eyre` error.
+pub(crate) async fn retry<F, Fut>(attempts: u32, mut send_request: F) -> Result<Response>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = reqwest::Result<Response>>,
+{
+    let attempts = attempts.max(1);
+    let mut last_err = None;
+
+    for attempt in 0..attempts {
+        match send_request().await {
+            Ok(resp) if !resp.status().is_server_error() => return Ok(resp),
+            Ok(resp) => {
+                warn!(
+                    "Attempt {}/{} got HTTP {}, retrying",
+                    attempt + 1,
+                    attempts,
+                    resp.status()
+                );
+                last_err = Some(eyre!("server error: HTTP {}", resp.status()));
+            }
+            Err(e) if e.is_connect() || e.is_timeout() => {
+                warn!("Attempt {}/{} failed: {}, retrying", attempt + 1, attempts, e);
+                last_err = Some(eyre!(e));
+            }
+            Err(e) => return Err(eyre!(e)),
+        }
+
+        if attempt + 1 < attempts {
+            tokio::time::sleep(backoff_delay(attempt)).await;
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| eyre!("request failed after {} attempts", attempts)))
+}
+
+/// Exponential backoff (200ms * 2^attempt) plus up to 100ms of jitter.
+fn backoff_delay(attempt: u32) -> Duration {
+    let base_ms = 200u64.saturating_mul(1u64 << attempt.min(16));
+    let jitter_ms = rand::thread_rng().gen_range(0..100);
+    Duration::from_millis(base_ms + jitter_ms)
+}